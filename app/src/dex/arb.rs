@@ -1,17 +1,28 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
 
 use anyhow::Result;
 use async_trait::async_trait;
 use penumbra_chain::component::StateReadExt;
-use penumbra_crypto::{asset, dex::execution::SwapExecution, Value, STAKING_TOKEN_ASSET_ID};
-use penumbra_storage::{StateDelta, StateWrite};
+use penumbra_crypto::{
+    asset,
+    dex::{execution::SwapExecution, DirectedTradingPair},
+    Value, STAKING_TOKEN_ASSET_ID,
+};
+use penumbra_storage::{StateDelta, StateRead, StateWrite};
 use tracing::instrument;
 
 use crate::dex::{
     router::{RouteAndFill, RoutingParams},
-    StateWriteExt,
+    PositionRead, StateWriteExt,
 };
 
+/// Object-store key under which in-block DEX execution accumulates the set of
+/// directed trading pairs touched by swaps or by newly opened positions. The arb
+/// search seeds its candidate set from these pairs, so only liquidity that
+/// actually moved this block is considered.
+pub const DEX_ACTIVITY_KEY: &str = "dex/arb/active_pairs";
+
 #[async_trait]
 pub trait Arbitrage: StateWrite + Sized {
     #[instrument(skip(self, arb_token, fixed_candidates))]
@@ -30,13 +41,65 @@ pub trait Arbitrage: StateWrite + Sized {
         // discover at the end that the arb wasn't profitable).
         let mut this = Arc::new(StateDelta::new(self.clone()));
 
-        // TODO: Build an extended candidate set with:
-        // - both ends of all trading pairs for which there were swaps in the block
-        // - both ends of all trading pairs for which positions were opened
-        let params = RoutingParams {
-            max_hops: 5,
-            price_limit: Some(1u64.into()),
-            fixed_candidates: Arc::new(fixed_candidates),
+        // Build the candidate set from in-block DEX activity: both ends of every
+        // trading pair that saw a swap or a position opening, unioned with any
+        // statically-configured candidates.
+        let active_pairs: BTreeSet<DirectedTradingPair> =
+            this.object_get(DEX_ACTIVITY_KEY).unwrap_or_default();
+
+        let mut candidates: BTreeSet<asset::Id> = fixed_candidates.iter().copied().collect();
+        candidates.insert(arb_token);
+        for pair in &active_pairs {
+            candidates.insert(pair.start);
+            candidates.insert(pair.end);
+        }
+
+        // Model the reachable pairs as a directed graph whose edge weight from `A`
+        // to `B` is `-ln(effective_price(A -> B))`, using the best available
+        // position price. A negative-weight cycle through the arb token then
+        // corresponds to a sequence of trades whose prices multiply to more than
+        // one, i.e. a profitable arbitrage.
+        let mut edges = Vec::new();
+        for &start in &candidates {
+            for &end in &candidates {
+                if start == end {
+                    continue;
+                }
+                let pair = DirectedTradingPair::new(start, end);
+                if let Some(price) = effective_price(this.as_ref(), &pair).await? {
+                    // Skip degenerate prices that can't contribute a finite weight.
+                    if price > 0.0 {
+                        edges.push((start, end, -price.ln()));
+                    }
+                }
+            }
+        }
+
+        let params = match find_negative_cycle(arb_token, &edges) {
+            Some(cycle) => {
+                // Route and fill along the detected cycle: we restrict the
+                // candidate set to exactly the assets on the cycle and route the
+                // arb token back to itself through them.
+                tracing::debug!(?cycle, "found candidate negative-weight cycle");
+                RoutingParams {
+                    max_hops: cycle.len(),
+                    price_limit: Some(1u64.into()),
+                    fixed_candidates: Arc::new(cycle),
+                }
+            }
+            None => {
+                // The single-best-position price graph found no negative cycle,
+                // but it can't see every profitable route; fall back to a
+                // route-and-fill over the full candidate set — the in-block
+                // activity pairs unioned with the statically-configured
+                // candidates, not the bare `fixed_candidates`.
+                tracing::debug!("no negative cycle detected, falling back to candidate route");
+                RoutingParams {
+                    max_hops: 5,
+                    price_limit: Some(1u64.into()),
+                    fixed_candidates: Arc::new(candidates.into_iter().collect()),
+                }
+            }
         };
 
         // Create a flash-loan 2^64 of the arb token to ourselves.
@@ -64,7 +127,9 @@ pub trait Arbitrage: StateWrite + Sized {
 
         if arb_profit == 0u64.into() {
             // If we didn't make any profit, we don't need to do anything,
-            // and we can just discard the state delta entirely.
+            // and we can just discard the state delta entirely. This also covers
+            // cycles whose realized profit rounds to zero once filled at integer
+            // precision, even though the graph search flagged them as negative.
             tracing::debug!("found 0-profit arb, discarding");
             return Ok(());
         }
@@ -109,4 +174,202 @@ pub trait Arbitrage: StateWrite + Sized {
     }
 }
 
-impl<T: StateWrite> Arbitrage for T {}
\ No newline at end of file
+/// Returns the effective price of the best position trading `pair.start` for
+/// `pair.end` — the amount of `end` obtained per unit of `start` — or `None` if
+/// there is no position on this pair.
+async fn effective_price<S: PositionRead + ?Sized>(
+    state: &S,
+    pair: &DirectedTradingPair,
+) -> Result<Option<f64>> {
+    let Some(position) = state.best_position(pair).await? else {
+        return Ok(None);
+    };
+
+    // Orient the position's trading function so that it describes selling
+    // `pair.start` for `pair.end`, then read off its effective price.
+    let effective_price = position.phi.orient_end(pair.end)?.effective_price();
+
+    Ok(Some(effective_price.into()))
+}
+
+/// Runs Bellman–Ford from `source` over `edges` and, if a negative-weight cycle
+/// through `source` is reachable, returns the assets on that cycle in traversal
+/// order, rotated to begin at `source`. Relaxes the edge set `|nodes| - 1` times
+/// and then checks for a further relaxation; the presence of one witnesses a
+/// negative cycle, which is recovered by walking predecessor pointers. Cycles
+/// that do not pass through `source` are skipped, since the arb flash-loans and
+/// repays `source`.
+fn find_negative_cycle(
+    source: asset::Id,
+    edges: &[(asset::Id, asset::Id, f64)],
+) -> Option<Vec<asset::Id>> {
+    let mut distance: BTreeMap<asset::Id, f64> = BTreeMap::new();
+    let mut predecessor: BTreeMap<asset::Id, asset::Id> = BTreeMap::new();
+
+    // Collect the node set, both to bound the number of relaxations and to bound
+    // the predecessor walk.
+    let mut nodes: BTreeSet<asset::Id> = BTreeSet::new();
+    for &(from, to, _) in edges {
+        nodes.insert(from);
+        nodes.insert(to);
+    }
+    nodes.insert(source);
+    distance.insert(source, 0.0);
+
+    // Relax every edge `|nodes| - 1` times.
+    for _ in 0..nodes.len().saturating_sub(1) {
+        for &(from, to, weight) in edges {
+            let Some(&from_distance) = distance.get(&from) else {
+                continue;
+            };
+            let relaxed = from_distance + weight;
+            if relaxed < *distance.get(&to).unwrap_or(&f64::INFINITY) {
+                distance.insert(to, relaxed);
+                predecessor.insert(to, from);
+            }
+        }
+    }
+
+    // One more pass: any edge that still relaxes lies on (or downstream of) a
+    // negative-weight cycle.
+    for &(from, to, weight) in edges {
+        let Some(&from_distance) = distance.get(&from) else {
+            continue;
+        };
+        if from_distance + weight < *distance.get(&to).unwrap_or(&f64::INFINITY) {
+            // Step `|nodes|` times along predecessors to land inside the cycle,
+            // then collect it.
+            let mut cursor = to;
+            for _ in 0..nodes.len() {
+                cursor = *predecessor.get(&cursor)?;
+            }
+
+            let start = cursor;
+            let mut cycle = vec![start];
+            let mut next = *predecessor.get(&start)?;
+            while next != start {
+                cycle.push(next);
+                next = *predecessor.get(&next)?;
+            }
+            cycle.reverse();
+
+            // Intentional restriction: we only execute a cycle that passes
+            // through the arb token, since the arb flash-loans and repays
+            // `source`. A negative cycle reachable from `source` but not on it
+            // (a source→…→cycle→…→source lasso) is profitable in principle but
+            // not expressible as a single self-repaying route here, so we skip it
+            // and keep scanning for a cycle through the source.
+            if let Some(offset) = cycle.iter().position(|asset| *asset == source) {
+                cycle.rotate_left(offset);
+                return Some(cycle);
+            } else {
+                tracing::debug!(
+                    ?cycle,
+                    "discarding negative cycle that does not pass through the arb token"
+                );
+            }
+        }
+    }
+
+    None
+}
+
+/// Write-side accumulation of in-block DEX activity for the arbitrage search.
+///
+/// [`record_active_pair`] adds a pair to the set keyed under
+/// [`DEX_ACTIVITY_KEY`] that [`Arbitrage::arbitrage`] seeds its candidate set
+/// from. The set holds only the pairs passed to this method; where it has not
+/// been called during the block, the arb search reads an empty set and uses
+/// only its fixed candidates and the arb token.
+///
+/// [`record_active_pair`]: RecordDexActivity::record_active_pair
+pub trait RecordDexActivity: StateWrite {
+    /// Record that `pair` was touched this block, in both directions, so that
+    /// both of its endpoints enter the arb candidate set.
+    fn record_active_pair(&mut self, pair: DirectedTradingPair) {
+        let mut active: BTreeSet<DirectedTradingPair> =
+            self.object_get(DEX_ACTIVITY_KEY).unwrap_or_default();
+        active.insert(pair);
+        active.insert(pair.flip());
+        self.object_put(DEX_ACTIVITY_KEY, active);
+    }
+}
+
+impl<T: StateWrite> RecordDexActivity for T {}
+
+impl<T: StateWrite> Arbitrage for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asset(unit: &str) -> asset::Id {
+        asset::REGISTRY.parse_unit(unit).id()
+    }
+
+    #[test]
+    fn recovers_negative_cycle_rotated_to_source() {
+        let penumbra = *STAKING_TOKEN_ASSET_ID;
+        let gm = asset("gm");
+        let gn = asset("gn");
+
+        // Prices of 2.0 around penumbra -> gm -> gn -> penumbra multiply to 8 > 1,
+        // so every edge weight is -ln(2) < 0 and the cycle is profitable.
+        let weight = -(2.0f64).ln();
+        let edges = vec![
+            (penumbra, gm, weight),
+            (gm, gn, weight),
+            (gn, penumbra, weight),
+        ];
+
+        let cycle = find_negative_cycle(penumbra, &edges).expect("a negative cycle exists");
+        assert_eq!(cycle.len(), 3);
+        assert_eq!(cycle[0], penumbra, "cycle is rotated to begin at the arb token");
+        assert!(cycle.contains(&gm) && cycle.contains(&gn));
+    }
+
+    #[test]
+    fn ignores_negative_cycle_not_through_source() {
+        let penumbra = *STAKING_TOKEN_ASSET_ID;
+        let gm = asset("gm");
+        let gn = asset("gn");
+
+        // A profitable cycle between gm and gn, with no edges into or out of the
+        // arb token, cannot be executed as a self-repaying route.
+        let weight = -(2.0f64).ln();
+        let edges = vec![(gm, gn, weight), (gn, gm, weight)];
+
+        assert!(find_negative_cycle(penumbra, &edges).is_none());
+    }
+
+    #[test]
+    fn finds_no_cycle_when_unprofitable() {
+        let penumbra = *STAKING_TOKEN_ASSET_ID;
+        let gm = asset("gm");
+
+        // Prices of 0.5 multiply to 0.25 < 1: no profitable cycle, all weights
+        // positive.
+        let weight = -(0.5f64).ln();
+        let edges = vec![(penumbra, gm, weight), (gm, penumbra, weight)];
+
+        assert!(find_negative_cycle(penumbra, &edges).is_none());
+    }
+
+    #[tokio::test]
+    async fn recording_a_swap_seeds_both_endpoints() {
+        let gm = asset("gm");
+        let gn = asset("gn");
+        let mut state = StateDelta::new(());
+
+        // A swap on the gm/gn pair records it; the arb search reads the same key.
+        state.record_active_pair(DirectedTradingPair::new(gm, gn));
+
+        let active: BTreeSet<DirectedTradingPair> = state
+            .object_get(DEX_ACTIVITY_KEY)
+            .expect("the swap populated the activity key");
+
+        // Both directions are recorded, so both endpoints enter the candidate set.
+        assert!(active.contains(&DirectedTradingPair::new(gm, gn)));
+        assert!(active.contains(&DirectedTradingPair::new(gn, gm)));
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::BTreeMap;
+
+use penumbra_crypto::{Balance, FullViewingKey, Note, Nullifier};
+use penumbra_tct as tct;
+use rand_core::{CryptoRng, RngCore};
+
+use super::SpendPlan;
+
+/// Coordinates note selection across the [`SpendPlan`]s built in a single
+/// planning session, so that a wallet assembling several transactions cannot
+/// select the same note twice and only discover the conflict once one spend's
+/// nullifier has already been committed on-chain.
+///
+/// Each scheduled plan yields a [`SpendEventuality`]: a lightweight record of the
+/// nullifier and the balance the spend is expected to contribute, which the
+/// caller reconciles against observed chain state to confirm the spend resolved
+/// — or releases, returning the note to the pool, if the transaction was dropped.
+#[derive(Clone, Debug, Default)]
+pub struct SpendScheduler {
+    /// The positions reserved by plans handed out this session, and the
+    /// nullifier each one will produce once spent.
+    reserved: BTreeMap<tct::Position, Nullifier>,
+}
+
+/// A handle to a scheduled spend, used to confirm or release it after the fact.
+#[derive(Clone, Debug)]
+pub struct SpendEventuality {
+    pub nullifier: Nullifier,
+    pub expected_balance_contribution: Balance,
+    position: tct::Position,
+}
+
+impl SpendScheduler {
+    /// Create a new, empty [`SpendScheduler`].
+    pub fn new() -> SpendScheduler {
+        Default::default()
+    }
+
+    /// Schedule a [`SpendPlan`] spending the given `position`ed `note`, returning
+    /// the plan together with its [`SpendEventuality`].
+    ///
+    /// Returns an error if the note at `position` has already been reserved by an
+    /// earlier plan in this session.
+    pub fn schedule<R: CryptoRng + RngCore>(
+        &mut self,
+        rng: &mut R,
+        fvk: &FullViewingKey,
+        note: Note,
+        position: tct::Position,
+    ) -> anyhow::Result<(SpendPlan, SpendEventuality)> {
+        if self.reserved.contains_key(&position) {
+            return Err(anyhow::anyhow!(
+                "note at position {:?} is already reserved by another spend plan",
+                position
+            ));
+        }
+
+        let plan = SpendPlan::new(rng, note, position);
+        let eventuality = SpendEventuality {
+            nullifier: plan.nullifier(fvk),
+            expected_balance_contribution: plan.balance(),
+            position,
+        };
+
+        self.reserved.insert(position, eventuality.nullifier);
+
+        Ok((plan, eventuality))
+    }
+
+    /// Returns whether the note at `position` is currently reserved.
+    pub fn is_reserved(&self, position: tct::Position) -> bool {
+        self.reserved.contains_key(&position)
+    }
+
+    /// Release the reservation held by `eventuality`, returning its note to the
+    /// pool. Call this when the transaction carrying the spend was dropped and
+    /// will never resolve.
+    pub fn release(&mut self, eventuality: &SpendEventuality) {
+        self.reserved.remove(&eventuality.position);
+    }
+
+    /// Reconcile a `nullifier` observed on-chain against the outstanding
+    /// reservations, confirming the corresponding spend resolved.
+    ///
+    /// Returns `true` if the nullifier matched a reservation (which is then
+    /// cleared), and `false` if it belongs to no scheduled spend.
+    pub fn confirm(&mut self, nullifier: Nullifier) -> bool {
+        let Some((&position, _)) = self
+            .reserved
+            .iter()
+            .find(|(_, reserved)| **reserved == nullifier)
+        else {
+            return false;
+        };
+
+        self.reserved.remove(&position);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use penumbra_crypto::{
+        keys::{SeedPhrase, SpendKey},
+        Address, Note, Rseed, Value, STAKING_TOKEN_ASSET_ID,
+    };
+    use rand_core::OsRng;
+
+    use super::*;
+
+    fn test_fvk() -> FullViewingKey {
+        let seed_phrase = SeedPhrase::generate(OsRng);
+        let spend_key = SpendKey::from_seed_phrase(seed_phrase, 0);
+        spend_key.full_viewing_key().clone()
+    }
+
+    fn test_note() -> Note {
+        Note::from_parts(
+            Address::dummy(&mut OsRng),
+            Value {
+                amount: 1u64.into(),
+                asset_id: *STAKING_TOKEN_ASSET_ID,
+            },
+            Rseed::generate(&mut OsRng),
+        )
+        .expect("test note is valid")
+    }
+
+    #[test]
+    fn scheduling_the_same_position_twice_is_rejected() {
+        let fvk = test_fvk();
+        let mut scheduler = SpendScheduler::new();
+        let position = tct::Position::from(0u64);
+
+        scheduler
+            .schedule(&mut OsRng, &fvk, test_note(), position)
+            .expect("first reservation succeeds");
+        assert!(scheduler.is_reserved(position));
+
+        scheduler
+            .schedule(&mut OsRng, &fvk, test_note(), position)
+            .expect_err("second reservation of the same position is rejected");
+    }
+
+    #[test]
+    fn confirming_a_nullifier_clears_its_reservation() {
+        let fvk = test_fvk();
+        let mut scheduler = SpendScheduler::new();
+
+        let (_, first) = scheduler
+            .schedule(&mut OsRng, &fvk, test_note(), tct::Position::from(0u64))
+            .expect("first reservation succeeds");
+        scheduler
+            .schedule(&mut OsRng, &fvk, test_note(), tct::Position::from(1u64))
+            .expect("second reservation succeeds");
+
+        assert!(scheduler.confirm(first.nullifier));
+        assert!(!scheduler.is_reserved(tct::Position::from(0u64)));
+        // The other reservation is untouched.
+        assert!(scheduler.is_reserved(tct::Position::from(1u64)));
+
+        // A nullifier that belongs to no reservation confirms nothing. It is
+        // derived from an unrelated note and key that were never scheduled, so
+        // it can't collide with the outstanding second reservation.
+        let bogus = SpendPlan::new(&mut OsRng, test_note(), tct::Position::from(99u64))
+            .nullifier(&test_fvk());
+        assert!(!scheduler.confirm(bogus));
+    }
+
+    #[test]
+    fn releasing_returns_the_note_to_the_pool() {
+        let fvk = test_fvk();
+        let mut scheduler = SpendScheduler::new();
+        let position = tct::Position::from(0u64);
+
+        let (_, eventuality) = scheduler
+            .schedule(&mut OsRng, &fvk, test_note(), position)
+            .expect("reservation succeeds");
+        assert!(scheduler.is_reserved(position));
+
+        scheduler.release(&eventuality);
+        assert!(!scheduler.is_reserved(position));
+
+        // After release, the position can be scheduled again.
+        scheduler
+            .schedule(&mut OsRng, &fvk, test_note(), position)
+            .expect("position is free to reserve again");
+    }
+}
@@ -25,87 +25,106 @@ pub mod update_client {
             client_is_not_frozen(&client_data)?;
             self.client_is_not_expired(&client_data).await?;
 
-            let trusted_client_state =
-                downcast!(client_data.client_state.0 => AnyClientState::Tendermint)
-                    .ok_or_else(|| anyhow::anyhow!("invalid client state: not Tendermint"))?;
-
-            let untrusted_header = downcast!(&msg.header => AnyHeader::Tendermint)
-                .ok_or_else(|| anyhow::anyhow!("invalid header: not Tendermint"))?;
-
-            // Optimization: reject duplicate updates instead of verifying them.
-            if self
-                .update_is_already_committed(&client_data.client_id, &untrusted_header)
-                .await?
-            {
-                // If the update is already committed, return an error to reject a duplicate update.
-                return Err(anyhow::anyhow!(
-                    "Client update has already been committed to the chain state"
-                ));
+            // Dispatch verification through the `ClientDef` registered for this
+            // client's type, so that heterogeneous light-client backends can be
+            // hosted side by side without special-casing them here.
+            match client_data.client_state.0.client_type() {
+                ClientType::Tendermint => {
+                    super::client_def::TendermintClient
+                        .verify_client_message(self, &client_data, &msg.header)
+                        .await
+                }
+                other => Err(anyhow::anyhow!(
+                    "no client definition registered for client type: {:?}",
+                    other
+                )),
             }
+        }
+    }
 
-            header_revision_matches_client_state(&trusted_client_state, &untrusted_header)?;
-            header_height_is_consistent(&untrusted_header)?;
-
-            // The (still untrusted) header uses the `trusted_height` field to
-            // specify the trusted anchor data it is extending.
-            let trusted_height = untrusted_header.trusted_height;
-
-            // We use the specified trusted height to query the trusted
-            // consensus state the update extends.
-            let last_trusted_consensus_state = self
-                .get_verified_consensus_state(trusted_height, client_data.client_id)
-                .await?
-                .as_tendermint()?;
-
-            // We also have to convert from an IBC height, which has two
-            // components, to a Tendermint height, which has only one.
-            let trusted_height = trusted_height
-                .revision_height
-                .try_into()
-                .map_err(|_| anyhow::anyhow!("invalid header height"))?;
-
-            let trusted_validator_set =
-                verify_header_validator_set(&untrusted_header, &last_trusted_consensus_state)?;
-
-            // Now we build the trusted and untrusted states to feed to the Tendermint light client.
+    /// The write side of a client update, run after [`UpdateClientCheck::validate`]
+    /// has succeeded.
+    ///
+    /// This dispatches through the client's [`ClientDef`](super::client_def::ClientDef)
+    /// to produce the updated client and consensus states, commits them, and
+    /// records the local processed-time and processed-height metadata that the
+    /// connection/channel delay period relies on.
+    #[async_trait]
+    pub trait UpdateClientExecute: StateExt {
+        async fn execute(&mut self, msg: &MsgUpdateAnyClient) -> anyhow::Result<()> {
+            use super::delay::DelayExt;
+
+            let client_data = self.get_client_data(&msg.client_id).await?;
+
+            let (new_client_state, new_consensus_state) =
+                match client_data.client_state.0.client_type() {
+                    ClientType::Tendermint => {
+                        let client_def = super::client_def::TendermintClient;
+
+                        // If the update conflicts with a consensus state already
+                        // committed at the same height, this is misbehaviour:
+                        // freeze the client instead of applying the update.
+                        if client_def
+                            .check_for_misbehaviour(self, &client_data, &msg.header)
+                            .await?
+                        {
+                            return self.freeze_client(&client_data, &msg.header).await;
+                        }
+
+                        client_def
+                            .check_header_and_update_state(self, &client_data, &msg.header)
+                            .await?
+                    }
+                    other => {
+                        return Err(anyhow::anyhow!(
+                            "no client definition registered for client type: {:?}",
+                            other
+                        ))
+                    }
+                };
+
+            // The height at which this consensus state is committed.
+            let height = new_client_state.0.latest_height();
+
+            // Commit the updated client state and the verified consensus state.
+            let mut client_data = client_data;
+            client_data.client_state = new_client_state;
+            self.put_client_data(client_data).await?;
+            self.put_verified_consensus_state(height, msg.client_id.clone(), new_consensus_state)
+                .await?;
+
+            // Record the local block timestamp and height at which this consensus
+            // state landed, so delay periods can be enforced later.
+            self.put_verified_consensus_state_metadata(&msg.client_id, height)
+                .await?;
 
-            let trusted_state = TrustedBlockState {
-                header_time: last_trusted_consensus_state.timestamp,
-                height: trusted_height,
-                next_validators: &trusted_validator_set,
-                next_validators_hash: last_trusted_consensus_state.next_validators_hash,
-            };
+            Ok(())
+        }
 
-            let untrusted_state = UntrustedBlockState {
-                signed_header: &untrusted_header.signed_header,
-                validators: &untrusted_header.validator_set,
-                next_validators: None, // TODO: do we need this?
-            };
+        /// Freeze `client_data` at the height of the conflicting `header`, so that
+        /// every subsequent update fails via the `client_is_not_frozen` guard.
+        async fn freeze_client(
+            &mut self,
+            client_data: &ClientData,
+            header: &AnyHeader,
+        ) -> anyhow::Result<()> {
+            let trusted_client_state =
+                downcast!(client_data.client_state.0.clone() => AnyClientState::Tendermint)
+                    .ok_or_else(|| anyhow::anyhow!("invalid client state: not Tendermint"))?;
 
-            let options = trusted_client_state.as_light_client_options()?;
-            let verifier = ProdVerifier::default();
+            let frozen_client_state = trusted_client_state.with_frozen_height(header.height())?;
 
-            let verdict = verifier.verify(
-                untrusted_state,
-                trusted_state,
-                &options,
-                self.get_block_timestamp().await?,
-            );
+            let mut client_data = client_data.clone();
+            client_data.client_state =
+                ClientState(AnyClientState::Tendermint(frozen_client_state));
+            self.put_client_data(client_data).await?;
 
-            match verdict {
-                Verdict::Success => Ok(()),
-                Verdict::NotEnoughTrust(voting_power_tally) => Err(anyhow::anyhow!(
-                    "not enough trust, voting power tally: {:?}",
-                    voting_power_tally
-                )),
-                Verdict::Invalid(detail) => Err(anyhow::anyhow!(
-                    "could not verify tendermint header: invalid: {:?}",
-                    detail
-                )),
-            }
+            Ok(())
         }
     }
 
+    impl<T: StateExt> UpdateClientExecute for T {}
+
     fn client_is_not_frozen(client: &ClientData) -> anyhow::Result<()> {
         if client.client_state.0.is_frozen() {
             Err(anyhow::anyhow!("client is frozen"))
@@ -114,7 +133,7 @@ pub mod update_client {
         }
     }
 
-    fn header_revision_matches_client_state(
+    pub(super) fn header_revision_matches_client_state(
         trusted_client_state: &TendermintClientState,
         untrusted_header: &TendermintHeader,
     ) -> anyhow::Result<()> {
@@ -127,7 +146,9 @@ pub mod update_client {
         }
     }
 
-    fn header_height_is_consistent(untrusted_header: &TendermintHeader) -> anyhow::Result<()> {
+    pub(super) fn header_height_is_consistent(
+        untrusted_header: &TendermintHeader,
+    ) -> anyhow::Result<()> {
         if untrusted_header.height() <= untrusted_header.trusted_height {
             Err(anyhow::anyhow!(
                 "client update height is not greater than trusted height"
@@ -137,7 +158,7 @@ pub mod update_client {
         }
     }
 
-    fn verify_header_validator_set<'h>(
+    pub(super) fn verify_header_validator_set<'h>(
         untrusted_header: &'h TendermintHeader,
         last_trusted_consensus_state: &TendermintConsensusState,
     ) -> anyhow::Result<&'h validator::Set> {
@@ -152,7 +173,33 @@ pub mod update_client {
         }
     }
 
-    mod inner {
+    /// Loads the stored Tendermint consensus state at `untrusted_header`'s height,
+    /// if any, and reports whether it matches the consensus state the header would
+    /// produce. `None` means no consensus state is stored at that height.
+    ///
+    /// This is the shared basis for both duplicate-update detection (a stored
+    /// state that *matches*) and misbehaviour detection (a stored state that
+    /// *differs*).
+    pub(super) async fn stored_consensus_state_matches<S: StateExt + ?Sized>(
+        ctx: &S,
+        client_id: &ClientId,
+        untrusted_header: &TendermintHeader,
+    ) -> anyhow::Result<Option<bool>> {
+        let untrusted_consensus_state = TendermintConsensusState::from(untrusted_header.clone());
+        if let Ok(stored_consensus_state) = ctx
+            .get_verified_consensus_state(untrusted_header.height(), client_id.clone())
+            .await
+        {
+            let stored_tm_consensus_state = stored_consensus_state.as_tendermint()?;
+            Ok(Some(stored_tm_consensus_state == untrusted_consensus_state))
+        } else {
+            // No consensus state at that height (missing or a DB error); not an
+            // error, just nothing stored to compare against.
+            Ok(None)
+        }
+    }
+
+    pub(super) mod inner {
         use super::*;
 
         #[async_trait]
@@ -195,23 +242,13 @@ pub mod update_client {
                 client_id: &ClientId,
                 untrusted_header: &TendermintHeader,
             ) -> anyhow::Result<bool> {
-                // check if we already have a consensus state for this height, if we do, check that it is
-                // the same as this update, if it is, return early.
-                let untrusted_consensus_state =
-                    TendermintConsensusState::from(untrusted_header.clone());
-                if let Ok(stored_consensus_state) = self
-                    .get_verified_consensus_state(untrusted_header.height(), client_id.clone())
-                    .await
-                {
-                    let stored_tm_consensus_state = stored_consensus_state.as_tendermint()?;
-
-                    Ok(stored_tm_consensus_state == untrusted_consensus_state)
-                } else {
-                    // If we don't have a consensus state for this height for
-                    // whatever reason (either missing or a DB error), we don't
-                    // consider it an error, it's just not already committed.
-                    Ok(false)
-                }
+                // An update is already committed iff a consensus state is stored
+                // at this height and it matches the one this header would produce.
+                Ok(matches!(
+                    super::stored_consensus_state_matches(self, client_id, untrusted_header)
+                        .await?,
+                    Some(true)
+                ))
             }
         }
 
@@ -220,3 +257,455 @@ pub mod update_client {
 
     impl<T: StateExt> UpdateClientCheck for T {}
 }
+
+pub mod client_def {
+    use super::super::*;
+
+    use super::update_client::inner::Inner;
+    use super::update_client::{
+        header_height_is_consistent, header_revision_matches_client_state,
+        verify_header_validator_set,
+    };
+
+    /// A light-client backend: the per-client-type verification logic that the
+    /// generic update path dispatches to.
+    ///
+    /// `UpdateClientCheck::validate` selects a `ClientDef` based on the stored
+    /// client's [`ClientType`] and defers header verification (validator-set
+    /// hashing, the light-client check, revision/height consistency) to it.
+    /// Frozen and expiry checks are not part of this trait: `validate` runs them
+    /// before dispatching, and the expiry check reads the latest consensus state
+    /// as a Tendermint consensus state. New client types — a proof-carrying
+    /// "guest" client for a rollup, a non-Tendermint consensus — implement this
+    /// trait and are picked up by adding an arm to the dispatches in
+    /// `UpdateClientCheck::validate` and `UpdateClientExecute::execute`.
+    #[async_trait]
+    pub trait ClientDef: Send + Sync {
+        /// Verifies that `header` is a valid update extending the trusted state of
+        /// the client described by `client_data`. Does not mutate state.
+        async fn verify_client_message<S: StateExt + ?Sized>(
+            &self,
+            ctx: &S,
+            client_data: &ClientData,
+            header: &AnyHeader,
+        ) -> anyhow::Result<()>;
+
+        /// Returns `true` if `header` conflicts with a consensus state already
+        /// committed for the client at the header's height.
+        async fn check_for_misbehaviour<S: StateExt + ?Sized>(
+            &self,
+            ctx: &S,
+            client_data: &ClientData,
+            header: &AnyHeader,
+        ) -> anyhow::Result<bool>;
+
+        /// Verifies `header` and returns the updated client and consensus states
+        /// it produces, for the write side to commit.
+        async fn check_header_and_update_state<S: StateExt + ?Sized>(
+            &self,
+            ctx: &S,
+            client_data: &ClientData,
+            header: &AnyHeader,
+        ) -> anyhow::Result<(ClientState, AnyConsensusState)>;
+    }
+
+    /// The CometBFT/Tendermint light-client backend.
+    pub struct TendermintClient;
+
+    #[async_trait]
+    impl ClientDef for TendermintClient {
+        async fn verify_client_message<S: StateExt + ?Sized>(
+            &self,
+            ctx: &S,
+            client_data: &ClientData,
+            header: &AnyHeader,
+        ) -> anyhow::Result<()> {
+            let untrusted_header = downcast!(header => AnyHeader::Tendermint)
+                .ok_or_else(|| anyhow::anyhow!("invalid header: not Tendermint"))?;
+
+            // Optimization: reject duplicate updates instead of verifying them.
+            if ctx
+                .update_is_already_committed(&client_data.client_id, &untrusted_header)
+                .await?
+            {
+                // If the update is already committed, return an error to reject a duplicate update.
+                return Err(anyhow::anyhow!(
+                    "Client update has already been committed to the chain state"
+                ));
+            }
+
+            // The actual consensus-level verification is shared with misbehaviour
+            // checking, which must run it even on headers that are already
+            // committed (the honest half of a double-sign is on-chain), so it
+            // lives in `verify_header` without the duplicate-update guard above.
+            self.verify_header(ctx, client_data, header).await
+        }
+
+        async fn check_for_misbehaviour<S: StateExt + ?Sized>(
+            &self,
+            ctx: &S,
+            client_data: &ClientData,
+            header: &AnyHeader,
+        ) -> anyhow::Result<bool> {
+            let untrusted_header = downcast!(header => AnyHeader::Tendermint)
+                .ok_or_else(|| anyhow::anyhow!("invalid header: not Tendermint"))?;
+
+            // Misbehaviour iff a consensus state is stored at this height and it
+            // differs from the one this header would produce: the counterparty
+            // committed to two different blocks at the same height.
+            Ok(matches!(
+                super::update_client::stored_consensus_state_matches(
+                    ctx,
+                    &client_data.client_id,
+                    &untrusted_header,
+                )
+                .await?,
+                Some(false)
+            ))
+        }
+
+        async fn check_header_and_update_state<S: StateExt + ?Sized>(
+            &self,
+            ctx: &S,
+            client_data: &ClientData,
+            header: &AnyHeader,
+        ) -> anyhow::Result<(ClientState, AnyConsensusState)> {
+            self.verify_client_message(ctx, client_data, header).await?;
+
+            let trusted_client_state =
+                downcast!(client_data.client_state.0.clone() => AnyClientState::Tendermint)
+                    .ok_or_else(|| anyhow::anyhow!("invalid client state: not Tendermint"))?;
+            let untrusted_header = downcast!(header => AnyHeader::Tendermint)
+                .ok_or_else(|| anyhow::anyhow!("invalid header: not Tendermint"))?;
+
+            let new_client_state = trusted_client_state.with_header(untrusted_header.clone())?;
+            let new_consensus_state =
+                TendermintConsensusState::from(untrusted_header.clone());
+
+            Ok((
+                ClientState(AnyClientState::Tendermint(new_client_state)),
+                AnyConsensusState::Tendermint(new_consensus_state),
+            ))
+        }
+    }
+
+    impl TendermintClient {
+        /// Runs the Tendermint consensus verification for `header` against the
+        /// trusted consensus state it claims to extend: validator-set hashing,
+        /// revision/height consistency, and the `ProdVerifier` light-client
+        /// check. Unlike [`verify_client_message`], it does *not* reject headers
+        /// whose consensus state is already committed, so it can be run against
+        /// misbehaviour evidence — where the honest header of a conflicting pair
+        /// is expected to already be on-chain.
+        ///
+        /// [`verify_client_message`]: ClientDef::verify_client_message
+        pub(crate) async fn verify_header<S: StateExt + ?Sized>(
+            &self,
+            ctx: &S,
+            client_data: &ClientData,
+            header: &AnyHeader,
+        ) -> anyhow::Result<()> {
+            let trusted_client_state =
+                downcast!(client_data.client_state.0.clone() => AnyClientState::Tendermint)
+                    .ok_or_else(|| anyhow::anyhow!("invalid client state: not Tendermint"))?;
+
+            let untrusted_header = downcast!(header => AnyHeader::Tendermint)
+                .ok_or_else(|| anyhow::anyhow!("invalid header: not Tendermint"))?;
+
+            header_revision_matches_client_state(&trusted_client_state, &untrusted_header)?;
+            header_height_is_consistent(&untrusted_header)?;
+
+            // The (still untrusted) header uses the `trusted_height` field to
+            // specify the trusted anchor data it is extending.
+            let trusted_height = untrusted_header.trusted_height;
+
+            // We use the specified trusted height to query the trusted
+            // consensus state the update extends.
+            let last_trusted_consensus_state = ctx
+                .get_verified_consensus_state(trusted_height, client_data.client_id.clone())
+                .await?
+                .as_tendermint()?;
+
+            // We also have to convert from an IBC height, which has two
+            // components, to a Tendermint height, which has only one.
+            let trusted_height = trusted_height
+                .revision_height
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("invalid header height"))?;
+
+            let trusted_validator_set =
+                verify_header_validator_set(&untrusted_header, &last_trusted_consensus_state)?;
+
+            // Now we build the trusted and untrusted states to feed to the Tendermint light client.
+
+            let trusted_state = TrustedBlockState {
+                header_time: last_trusted_consensus_state.timestamp,
+                height: trusted_height,
+                next_validators: &trusted_validator_set,
+                next_validators_hash: last_trusted_consensus_state.next_validators_hash,
+            };
+
+            let untrusted_state = UntrustedBlockState {
+                signed_header: &untrusted_header.signed_header,
+                validators: &untrusted_header.validator_set,
+                next_validators: None, // TODO: do we need this?
+            };
+
+            let options = trusted_client_state.as_light_client_options()?;
+            let verifier = ProdVerifier::default();
+
+            let verdict = verifier.verify(
+                untrusted_state,
+                trusted_state,
+                &options,
+                ctx.get_block_timestamp().await?,
+            );
+
+            match verdict {
+                Verdict::Success => Ok(()),
+                Verdict::NotEnoughTrust(voting_power_tally) => Err(anyhow::anyhow!(
+                    "not enough trust, voting power tally: {:?}",
+                    voting_power_tally
+                )),
+                Verdict::Invalid(detail) => Err(anyhow::anyhow!(
+                    "could not verify tendermint header: invalid: {:?}",
+                    detail
+                )),
+            }
+        }
+    }
+}
+
+pub mod submit_misbehaviour {
+    use super::super::*;
+
+    /// Checks a [`MsgSubmitMisbehaviour`] reporting that a counterparty chain has
+    /// equivocated, and freezes the offending client if the evidence holds up.
+    ///
+    /// This is the equivocation-reporting counterpart to
+    /// [`UpdateClientCheck`](super::update_client::UpdateClientCheck): where that
+    /// trait verifies a single header extending the trusted state, this one
+    /// verifies two conflicting headers and, if both are individually valid,
+    /// establishes that the counterparty either double-signed (two different
+    /// blocks at one height) or broke time monotonicity (a lower-height header
+    /// timestamped after the higher-height one).
+    #[async_trait]
+    pub trait MisbehaviourCheck: StateExt {
+        async fn validate(&mut self, msg: &MsgSubmitMisbehaviour) -> anyhow::Result<()> {
+            let client_data = self.get_client_data(&msg.client_id).await?;
+
+            // A frozen client cannot be frozen again: the evidence is moot.
+            if client_data.client_state.0.is_frozen() {
+                return Err(anyhow::anyhow!("client is already frozen"));
+            }
+
+            let trusted_client_state =
+                downcast!(client_data.client_state.0.clone() => AnyClientState::Tendermint)
+                    .ok_or_else(|| anyhow::anyhow!("invalid client state: not Tendermint"))?;
+
+            let header1 = downcast!(&msg.header1 => AnyHeader::Tendermint)
+                .ok_or_else(|| anyhow::anyhow!("invalid header: not Tendermint"))?;
+            let header2 = downcast!(&msg.header2 => AnyHeader::Tendermint)
+                .ok_or_else(|| anyhow::anyhow!("invalid header: not Tendermint"))?;
+
+            // Each header must verify against the trusted consensus state it
+            // claims to extend. We call the bare `verify_header` rather than
+            // `verify_client_message`, because the latter rejects any header
+            // whose consensus state is already committed — and in the canonical
+            // double-sign case the honest header of the conflicting pair is
+            // exactly the one already on-chain.
+            let client_def = super::client_def::TendermintClient;
+            client_def
+                .verify_header(self, &client_data, &msg.header1)
+                .await?;
+            client_def
+                .verify_header(self, &client_data, &msg.header2)
+                .await?;
+
+            // With both headers verified, determine whether they actually
+            // constitute misbehaviour.
+            let frozen_height = if header1.height() == header2.height() {
+                // (a) Double-signing / fork: two valid headers at the same height
+                // committing to different blocks.
+                if header1.signed_header.commit.block_id == header2.signed_header.commit.block_id {
+                    return Err(anyhow::anyhow!(
+                        "headers are identical at height {}, no misbehaviour",
+                        header1.height()
+                    ));
+                }
+                header1.height()
+            } else {
+                // (b) Time monotonicity violation: the header at the lower (or
+                // equal) height carries a later `header_time` than the header at
+                // the greater height. The greater-height reference is taken from
+                // the consensus state *stored in state* at that height when one
+                // has been committed, falling back to the evidence header's own
+                // time otherwise — so the check fires both against an on-chain
+                // consensus state and against a bare two-header equivocation.
+                let (lower, higher) = if header1.height() < header2.height() {
+                    (header1, header2)
+                } else {
+                    (header2, header1)
+                };
+
+                let higher_time = match self
+                    .get_verified_consensus_state(higher.height(), msg.client_id.clone())
+                    .await
+                {
+                    Ok(stored) => stored.as_tendermint()?.timestamp,
+                    Err(_) => higher.signed_header.header.time,
+                };
+
+                if lower.signed_header.header.time <= higher_time {
+                    return Err(anyhow::anyhow!(
+                        "headers do not violate time monotonicity, no misbehaviour"
+                    ));
+                }
+                // Freeze at the lower of the two heights.
+                lower.height()
+            };
+
+            // Freeze the client at the offending height so every subsequent
+            // `update_client` fails via the `client_is_not_frozen` guard.
+            let frozen_client_state = trusted_client_state.with_frozen_height(frozen_height)?;
+            let mut client_data = client_data;
+            client_data.client_state = ClientState(AnyClientState::Tendermint(frozen_client_state));
+            self.put_client_data(client_data).await?;
+
+            Ok(())
+        }
+    }
+
+    impl<T: StateExt> MisbehaviourCheck for T {}
+}
+
+pub mod delay {
+    use super::super::*;
+
+    use core::time::Duration;
+
+    fn processed_time_key(client_id: &ClientId, height: Height) -> String {
+        format!(
+            "ibc_client/{}/processedTimes/{}",
+            client_id, height
+        )
+    }
+
+    fn processed_height_key(client_id: &ClientId, height: Height) -> String {
+        format!(
+            "ibc_client/{}/processedHeights/{}",
+            client_id, height
+        )
+    }
+
+    /// Processed-time / processed-height bookkeeping for verified consensus states.
+    ///
+    /// IBC connection and channel delay periods require that a minimum amount of
+    /// wall-clock time *and* a minimum number of local blocks have elapsed since a
+    /// counterparty header was committed. We cannot read that from the consensus
+    /// state itself — it records the counterparty's clock, not ours — so each time
+    /// a header lands we stamp it with our local block timestamp and height, keyed
+    /// by `(client_id, height)`, and consult those records when a packet requires
+    /// a delay.
+    #[async_trait]
+    pub trait DelayExt: StateExt {
+        /// Records the local block timestamp and height at which the consensus
+        /// state for `(client_id, height)` was committed.
+        ///
+        /// Called on the write side, after [`UpdateClientCheck::validate`] has
+        /// succeeded and the verified consensus state has been persisted.
+        ///
+        /// [`UpdateClientCheck::validate`]: super::update_client::UpdateClientCheck::validate
+        async fn put_verified_consensus_state_metadata(
+            &mut self,
+            client_id: &ClientId,
+            height: Height,
+        ) -> anyhow::Result<()> {
+            let processed_time = self.get_block_timestamp().await?;
+            let processed_height = self.get_block_height().await?;
+
+            self.put_raw(
+                processed_time_key(client_id, height),
+                processed_time
+                    .unix_timestamp_nanos()
+                    .to_be_bytes()
+                    .to_vec(),
+            );
+            self.put_raw(
+                processed_height_key(client_id, height),
+                processed_height.to_be_bytes().to_vec(),
+            );
+
+            Ok(())
+        }
+
+        /// Reads the local block timestamp at which `(client_id, height)` was
+        /// committed, as a number of nanoseconds since the Unix epoch.
+        async fn get_client_processed_time(
+            &self,
+            client_id: &ClientId,
+            height: Height,
+        ) -> anyhow::Result<i128> {
+            let bytes = self
+                .get_raw(&processed_time_key(client_id, height))
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("no processed time for consensus state"))?;
+
+            Ok(i128::from_be_bytes(bytes.as_slice().try_into()?))
+        }
+
+        /// Reads the local block height at which `(client_id, height)` was
+        /// committed.
+        async fn get_client_processed_height(
+            &self,
+            client_id: &ClientId,
+            height: Height,
+        ) -> anyhow::Result<u64> {
+            let bytes = self
+                .get_raw(&processed_height_key(client_id, height))
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("no processed height for consensus state"))?;
+
+            Ok(u64::from_be_bytes(bytes.as_slice().try_into()?))
+        }
+
+        /// Returns `Ok(())` only once both the time delay and the block delay have
+        /// elapsed since `(client_id, height)` was committed locally.
+        async fn verify_delay_passed(
+            &self,
+            client_id: &ClientId,
+            height: Height,
+            time_delay: Duration,
+            block_delay: u64,
+        ) -> anyhow::Result<()> {
+            let processed_time = self.get_client_processed_time(client_id, height).await?;
+            let processed_height = self.get_client_processed_height(client_id, height).await?;
+
+            let now = self.get_block_timestamp().await?.unix_timestamp_nanos();
+            let current_height = self.get_block_height().await?;
+
+            // Clamp to zero: if the local clock has regressed below the processed
+            // time (skew), a negative `i128` must not wrap to a huge `u128` and
+            // let the fraud-window check pass spuriously.
+            let elapsed_time = now.saturating_sub(processed_time).max(0);
+            if (elapsed_time as u128) < time_delay.as_nanos() {
+                return Err(anyhow::anyhow!(
+                    "time delay period has not yet passed for consensus state at height {}",
+                    height
+                ));
+            }
+
+            let elapsed_blocks = current_height.saturating_sub(processed_height);
+            if elapsed_blocks < block_delay {
+                return Err(anyhow::anyhow!(
+                    "block delay period has not yet passed for consensus state at height {}",
+                    height
+                ));
+            }
+
+            Ok(())
+        }
+    }
+
+    impl<T: StateExt> DelayExt for T {}
+}